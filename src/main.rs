@@ -1,19 +1,172 @@
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use pubky::{recovery_file, Keypair};
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use std::fs::File;
 use std::io::Write;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
 
+// z-base32 alphabet: each character encodes 5 bits, most-significant-bit first.
+const ZBASE32_ALPHABET: &[u8] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+
+// An ed25519 public key is 32 bytes (256 bits), and its full z-base32 form is
+// 52 characters (PUBKY_STRING_LEN below). 51 z-base32 characters cover 255 of
+// those bits; a 52nd character would need a 33rd byte, which doesn't exist, so
+// the raw-byte fast path in matches_prefix can't represent a 52-char prefix.
+// An exact 52-char match is infeasible to grind for in practice anyway, so
+// this is the longest prefix worth accepting.
+const MAX_PREFIX_CHARS: usize = 51;
+
 fn is_valid_zbase32_char(c: char) -> bool {
-    // z-base32 alphabet: ybndrfg8ejkmcpqxot1uwisza345h769
     matches!(c, 'y' | 'b' | 'n' | 'd' | 'r' | 'f' | 'g' | '8' | 'e' | 'j' | 'k' |
              'm' | 'c' | 'p' | 'q' | 'x' | 'o' | 't' | '1' | 'u' | 'w' | 'i' |
              's' | 'z' | 'a' | '3' | '4' | '5' | 'h' | '7' | '6' | '9')
 }
 
+fn zbase32_char_value(c: char) -> u8 {
+    ZBASE32_ALPHABET.iter().position(|&b| b as char == c).unwrap() as u8
+}
+
+/// Packs a z-base32 prefix into a target/mask byte pair over the raw 32-byte
+/// ed25519 public key, so a candidate can be checked with `pubkey[i] & mask[i]
+/// == target[i]` instead of formatting and comparing strings on every
+/// attempt (the technique wireguard-vanity-key uses for its own prefixes).
+fn zbase32_prefix_target_mask(prefix: &str) -> (Vec<u8>, Vec<u8>) {
+    let bit_count = prefix.chars().count() * 5;
+    let byte_count = (bit_count + 7) / 8;
+    let mut target = vec![0u8; byte_count];
+    let mut mask = vec![0u8; byte_count];
+
+    let mut bit_pos = 0usize;
+    for c in prefix.chars() {
+        let value = zbase32_char_value(c);
+        for shift in (0..5).rev() {
+            let byte_idx = bit_pos / 8;
+            let bit_idx = 7 - (bit_pos % 8);
+            if (value >> shift) & 1 == 1 {
+                target[byte_idx] |= 1 << bit_idx;
+            }
+            mask[byte_idx] |= 1 << bit_idx;
+            bit_pos += 1;
+        }
+    }
+
+    (target, mask)
+}
+
+/// A single `--grind` request: match a public key whose z-base32 form starts
+/// with `starts` and/or ends with `ends`, and keep looking until `count`
+/// instances have been found. Mirrors Solana keygen's `GrindMatch`.
+struct GrindMatch {
+    starts: String,
+    ends: String,
+    count: AtomicU64,
+    prefix_target: Vec<u8>,
+    prefix_mask: Vec<u8>,
+}
+
+impl GrindMatch {
+    fn new(starts: String, ends: String, count: u64) -> Self {
+        let (prefix_target, prefix_mask) = zbase32_prefix_target_mask(&starts);
+        Self {
+            starts,
+            ends,
+            count: AtomicU64::new(count),
+            prefix_target,
+            prefix_mask,
+        }
+    }
+
+    /// Cheap raw-byte check against the desired prefix; safe to call on
+    /// every candidate keypair since it never formats a string.
+    fn matches_prefix(&self, pubkey_bytes: &[u8]) -> bool {
+        if self.prefix_mask.len() > pubkey_bytes.len() {
+            return false;
+        }
+        self.prefix_mask
+            .iter()
+            .enumerate()
+            .all(|(i, &mask)| (pubkey_bytes[i] & mask) == self.prefix_target[i])
+    }
+
+    /// Suffix check; only worth calling once the (cheap) prefix check passes,
+    /// since it needs the z-base32 string form of the candidate key.
+    fn matches_suffix(&self, lower_pubky: &str) -> bool {
+        self.ends.is_empty() || lower_pubky.ends_with(&self.ends)
+    }
+
+    /// `--contains` mode: both patterns may appear anywhere in the key
+    /// instead of being anchored to the start/end.
+    fn matches_anywhere(&self, lower_pubky: &str) -> bool {
+        (self.starts.is_empty() || lower_pubky.contains(&self.starts))
+            && (self.ends.is_empty() || lower_pubky.contains(&self.ends))
+    }
+
+    fn is_complete(&self) -> bool {
+        self.count.load(Ordering::Relaxed) == 0
+    }
+
+    fn label(&self) -> String {
+        match (self.starts.is_empty(), self.ends.is_empty()) {
+            (false, false) => format!("{}...{}", self.starts, self.ends),
+            (false, true) => self.starts.clone(),
+            (true, false) => self.ends.clone(),
+            (true, true) => String::new(),
+        }
+    }
+}
+
+/// Parses a `starts:ends:count` triple from `--grind` into a `GrindMatch`,
+/// e.g. `pk::3` (three keys ending in "pk") or `abc:xyz:1`.
+fn parse_grind_match(spec: &str) -> Result<GrindMatch, String> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    if parts.len() != 3 {
+        return Err(format!(
+            "Invalid --grind value '{}': expected format starts:ends:count",
+            spec
+        ));
+    }
+
+    let starts = parts[0].to_lowercase();
+    let ends = parts[1].to_lowercase();
+    let count_str = parts[2];
+
+    if starts.is_empty() && ends.is_empty() {
+        return Err(format!(
+            "Invalid --grind value '{}': at least one of starts or ends must be non-empty",
+            spec
+        ));
+    }
+
+    for (label, pattern) in [("starts", &starts), ("ends", &ends)] {
+        if let Some(invalid) = pattern.chars().find(|&c| !is_valid_zbase32_char(c)) {
+            return Err(format!(
+                "Invalid --grind {} pattern '{}': character '{}' is not valid z-base32",
+                label, pattern, invalid
+            ));
+        }
+    }
+
+    if starts.chars().count() > MAX_PREFIX_CHARS {
+        return Err(format!(
+            "Invalid --grind starts pattern '{}': longer than {} characters, which can never match a 32-byte key",
+            starts, MAX_PREFIX_CHARS
+        ));
+    }
+
+    let count: u64 = count_str
+        .parse()
+        .map_err(|_| format!("Invalid --grind count '{}': must be a positive integer", count_str))?;
+
+    if count == 0 {
+        return Err(format!("Invalid --grind value '{}': count must be at least 1", spec));
+    }
+
+    Ok(GrindMatch::new(starts, ends, count))
+}
+
 pub fn get_secret_key_from_keypair(keypair: &Keypair) -> String {
     hex::encode(keypair.secret_key())
 }
@@ -38,17 +191,120 @@ pub fn save_recovery_file(keypair: &Keypair, passphrase: &str) -> Vec<u8> {
     recovery_file::create_recovery_file(keypair, passphrase)
 }
 
+/// Number of z-base32 characters a `GrindMatch` constrains (start + end).
+fn pattern_length(grind_match: &GrindMatch) -> usize {
+    grind_match.starts.chars().count() + grind_match.ends.chars().count()
+}
+
+/// Expected number of random attempts to hit a pattern of `length`
+/// z-base32 characters: each character has a 1-in-32 chance, so the
+/// expected number of tries is 32^length and the median is ln(2)*32^length.
+/// This assumes the pattern is anchored (prefix/suffix matching).
+fn expected_attempts(length: usize) -> f64 {
+    32f64.powi(length as i32)
+}
+
+/// A 32-byte ed25519 public key's z-base32 form is 52 characters long
+/// (256 bits / 5 bits-per-char, rounded up).
+const PUBKY_STRING_LEN: usize = 52;
+
+/// Expected number of attempts to hit a pattern of `length` characters
+/// appearing anywhere in the encoded key (`--contains` mode), rather than
+/// anchored to the start or end. A substring of length `length` has
+/// `PUBKY_STRING_LEN - length + 1` candidate starting positions per
+/// attempt, so this divides the anchored estimate down accordingly; it's
+/// an approximation (positions aren't fully independent) but is far
+/// closer than the anchored figure, which overstates contains-mode
+/// difficulty by roughly that same factor.
+fn expected_attempts_contains(length: usize) -> f64 {
+    if length == 0 || length > PUBKY_STRING_LEN {
+        return expected_attempts(length);
+    }
+    let positions = (PUBKY_STRING_LEN - length + 1) as f64;
+    expected_attempts(length) / positions
+}
+
+/// Refuses to clobber an existing file unless `force` is set, mirroring
+/// Solana keygen's `check_for_overwrite`. `-` (stdout) is never a file on
+/// disk, so it's always allowed.
+fn check_for_overwrite(path: &str, force: bool) -> Result<(), String> {
+    if path != "-" && !force && std::path::Path::new(path).exists() {
+        return Err(format!(
+            "refusing to overwrite existing file '{}' (use --force to overwrite)",
+            path
+        ));
+    }
+    Ok(())
+}
+
+fn mnemonic_type_from_word_count(word_count: usize) -> MnemonicType {
+    match word_count {
+        24 => MnemonicType::Words24,
+        _ => MnemonicType::Words12,
+    }
+}
+
+/// Derives the 32-byte ed25519 seed (and therefore the `Keypair`) for a BIP39
+/// mnemonic and optional seed passphrase, the same way Solana keygen turns a
+/// recovery phrase back into a signing key.
+pub fn keypair_from_mnemonic(mnemonic: &Mnemonic, seed_passphrase: &str) -> Keypair {
+    let seed = Seed::new(mnemonic, seed_passphrase);
+    let seed_bytes: [u8; 32] = seed.as_bytes()[..32].try_into().unwrap();
+    Keypair::from_secret_key(&seed_bytes)
+}
+
+/// Handles the `restore` subcommand: turns a previously saved recovery phrase
+/// back into the same `Keypair` it was generated from.
+fn run_restore(phrase: &str, seed_passphrase: &str) {
+    let mnemonic = match Mnemonic::from_phrase(phrase.trim(), Language::English) {
+        Ok(mnemonic) => mnemonic,
+        Err(e) => {
+            eprintln!("Error: Invalid recovery phrase: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let keypair = keypair_from_mnemonic(&mnemonic, seed_passphrase);
+
+    println!("Public key: {}", keypair.public_key());
+    println!("Private key: {}", get_secret_key_from_keypair(&keypair));
+}
+
 fn main() {
     // Parse command line arguments using clap
     let matches = Command::new("Vanity Pubky Generator")
         .version("1.0")
         .about("Generates public keys with a specified vanity prefix")
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("restore")
+                .about("Restores a Keypair from a previously saved BIP39 recovery phrase")
+                .arg(
+                    Arg::new("phrase")
+                        .help("The 12- or 24-word recovery phrase (quote it as a single argument)")
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("seed_passphrase")
+                        .long("seed-passphrase")
+                        .help("BIP39 passphrase used when the phrase was generated (defaults to none)")
+                        .value_name("PASSPHRASE")
+                )
+        )
         .arg(
             Arg::new("vanity_name")
-                .help("The desired vanity prefix for the public key")
-                .required(true)
+                .help("The desired vanity prefix for the public key (shorthand for --grind NAME::1)")
+                .required_unless_present("grind")
                 .index(1)
         )
+        .arg(
+            Arg::new("grind")
+                .long("grind")
+                .help("Repeatable vanity pattern in the form starts:ends:count, e.g. pk::3 or abc:xyz:1")
+                .value_name("STARTS:ENDS:COUNT")
+                .action(ArgAction::Append)
+        )
         .arg(
             Arg::new("threads")
                 .long("threads")
@@ -63,49 +319,129 @@ fn main() {
                 .help("Passphrase for the recovery file (defaults to 'password')")
                 .value_name("PASSPHRASE")
         )
+        .arg(
+            Arg::new("use_mnemonic")
+                .long("use-mnemonic")
+                .help("Derive each candidate keypair from a fresh BIP39 mnemonic instead of raw randomness, \
+                       so a hit comes with a recovery phrase. This runs PBKDF2 on every attempt and is \
+                       dramatically slower; leave it off to grind at full speed and restore only the hex key.")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("word_count")
+                .long("word-count")
+                .help("Number of words in the generated BIP39 recovery phrase (only used with --use-mnemonic)")
+                .value_name("12|24")
+                .value_parser(["12", "24"])
+                .default_value("12")
+        )
+        .arg(
+            Arg::new("seed_passphrase")
+                .long("seed-passphrase")
+                .help("Optional BIP39 passphrase applied on top of the recovery phrase (only used with --use-mnemonic)")
+                .value_name("PASSPHRASE")
+        )
+        .arg(
+            Arg::new("contains")
+                .long("contains")
+                .visible_alias("anywhere")
+                .help("Match patterns anywhere in the z-base32 public key, instead of only as a prefix/suffix")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .help("Path to write the recovery file to ('-' streams it to stdout); defaults to a name derived from the match")
+                .value_name("PATH")
+                .conflicts_with("no_outfile")
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Overwrite an existing recovery file instead of refusing")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("no_outfile")
+                .long("no-outfile")
+                .help("Don't write a recovery file at all; only print the keys (and the recovery phrase with --use-mnemonic)")
+                .action(ArgAction::SetTrue)
+        )
         .get_matches();
 
-    // Get the required vanity name
-    let raw_vanity_name = matches
-        .get_one::<String>("vanity_name")
-        .unwrap();
+    if let Some(restore_matches) = matches.subcommand_matches("restore") {
+        let phrase = restore_matches.get_one::<String>("phrase").unwrap();
+        let seed_passphrase = restore_matches
+            .get_one::<String>("seed_passphrase")
+            .map(|s| s.as_str())
+            .unwrap_or("");
+        run_restore(phrase, seed_passphrase);
+        return;
+    }
 
-    // Trim any leading or trailing spaces
-    let trimmed_vanity_name = raw_vanity_name.trim();
+    // Build the list of grind matches, either from the positional shorthand
+    // or from one or more repeatable --grind specs.
+    let mut grind_matches: Vec<GrindMatch> = Vec::new();
 
-    // Check if the trimmed string contains any spaces
-    if trimmed_vanity_name.contains(' ') {
-        eprintln!("Error: Vanity name cannot contain spaces.");
-        std::process::exit(1);
-    }
+    if let Some(raw_vanity_name) = matches.get_one::<String>("vanity_name") {
+        // Trim any leading or trailing spaces
+        let trimmed_vanity_name = raw_vanity_name.trim();
 
-    // If the trimmed string is empty, exit with an error
-    if trimmed_vanity_name.is_empty() {
-        eprintln!("Error: Vanity name cannot be empty.");
-        std::process::exit(1);
-    }
+        // Check if the trimmed string contains any spaces
+        if trimmed_vanity_name.contains(' ') {
+            eprintln!("Error: Vanity name cannot contain spaces.");
+            std::process::exit(1);
+        }
 
-    // Check if all characters in the vanity name are valid z-base32 characters
-    let invalid_chars: Vec<char> = trimmed_vanity_name
-        .chars()
-        .filter(|&c| !is_valid_zbase32_char(c.to_ascii_lowercase()))
-        .collect();
+        // If the trimmed string is empty, exit with an error
+        if trimmed_vanity_name.is_empty() {
+            eprintln!("Error: Vanity name cannot be empty.");
+            std::process::exit(1);
+        }
 
-    if !invalid_chars.is_empty() {
-        eprintln!("Error: Vanity name contains invalid characters: {:?}", invalid_chars);
-        eprintln!("Valid characters are: ybndrfg8ejkmcpqxot1uwisza345h769");
-        eprintln!("Invalid characters that cannot be used: v0l2");
-        std::process::exit(1);
-    }
+        // Check if all characters in the vanity name are valid z-base32 characters
+        let invalid_chars: Vec<char> = trimmed_vanity_name
+            .chars()
+            .filter(|&c| !is_valid_zbase32_char(c.to_ascii_lowercase()))
+            .collect();
+
+        if !invalid_chars.is_empty() {
+            eprintln!("Error: Vanity name contains invalid characters: {:?}", invalid_chars);
+            eprintln!("Valid characters are: ybndrfg8ejkmcpqxot1uwisza345h769");
+            eprintln!("Invalid characters that cannot be used: v0l2");
+            std::process::exit(1);
+        }
 
-    // Convert to lowercase for case-insensitive matching
-    let desired_prefix = trimmed_vanity_name.to_lowercase();
+        // If the original string had spaces that were trimmed, inform the user
+        if raw_vanity_name != trimmed_vanity_name {
+            eprintln!("Note: Leading/trailing spaces have been trimmed from the vanity name.");
+        }
 
-    // If the original string had spaces that were trimmed, inform the user
-    if raw_vanity_name != trimmed_vanity_name {
-        println!("Note: Leading/trailing spaces have been trimmed from the vanity name.");
+        if trimmed_vanity_name.chars().count() > MAX_PREFIX_CHARS {
+            eprintln!(
+                "Error: Vanity name is longer than {} characters, which can never match a 32-byte key.",
+                MAX_PREFIX_CHARS
+            );
+            std::process::exit(1);
+        }
+
+        grind_matches.push(GrindMatch::new(trimmed_vanity_name.to_lowercase(), String::new(), 1));
     }
 
+    if let Some(specs) = matches.get_many::<String>("grind") {
+        for spec in specs {
+            match parse_grind_match(spec) {
+                Ok(grind_match) => grind_matches.push(grind_match),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    let grind_matches = Arc::new(grind_matches);
+
     // Get the optional number of threads with default as CPU count
     let num_threads = matches
         .get_one::<String>("threads")
@@ -120,37 +456,129 @@ fn main() {
     let passphrase = matches
         .get_one::<String>("passphrase")
         .map(|s| s.as_str())
-        .unwrap_or("password");
+        .unwrap_or("password")
+        .to_string();
+
+    // BIP39 recovery phrase settings for the keys we generate
+    let word_count: usize = matches
+        .get_one::<String>("word_count")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mnemonic_type = mnemonic_type_from_word_count(word_count);
+    let seed_passphrase = matches
+        .get_one::<String>("seed_passphrase")
+        .map(|s| s.as_str())
+        .unwrap_or("")
+        .to_string();
+    let use_mnemonic = matches.get_flag("use_mnemonic");
+
+    let contains_mode = matches.get_flag("contains");
+
+    // Output controls for the recovery file: a custom path (or '-' for
+    // stdout), an overwrite guard, and an opt-out that skips the file
+    // entirely and only prints the keys.
+    let outfile = matches.get_one::<String>("outfile").cloned();
+    let force = matches.get_flag("force");
+    let no_outfile = matches.get_flag("no_outfile");
+
+    // An explicit --outfile names a single file, which only makes sense
+    // when exactly one match is expected overall; otherwise later hits
+    // would either get refused (without --force) or silently clobber
+    // earlier ones (with --force).
+    let total_expected_matches: u64 = grind_matches
+        .iter()
+        .map(|m| m.count.load(Ordering::Relaxed))
+        .sum();
+    if outfile.is_some() && total_expected_matches > 1 {
+        eprintln!(
+            "Error: --outfile names a single file but {} matches were requested in total; \
+             drop --outfile (to use the derived per-match name) or reduce the grind to a single match.",
+            total_expected_matches
+        );
+        std::process::exit(1);
+    }
 
-    println!("Generating public key with prefix: {}", desired_prefix);
-    println!("Using {} threads", num_threads);
-    println!("Using passphrase: {}",
+    eprintln!("Generating public keys for {} pattern(s):", grind_matches.len());
+    for grind_match in grind_matches.iter() {
+        let length = pattern_length(grind_match);
+        let expected = if contains_mode {
+            expected_attempts_contains(length)
+        } else {
+            expected_attempts(length)
+        };
+        let estimate_label = if contains_mode { " (contains-adjusted estimate)" } else { "" };
+        eprintln!(
+            "  - starts:\"{}\" ends:\"{}\" count:{} (~{:.0} expected attempts, ~{:.0} median{})",
+            grind_match.starts,
+            grind_match.ends,
+            grind_match.count.load(Ordering::Relaxed),
+            expected,
+            expected * std::f64::consts::LN_2,
+            estimate_label,
+        );
+    }
+    eprintln!("Using {} threads", num_threads);
+    eprintln!("Using passphrase: {}",
              if has_passphrase { "provided" } else { ": default" });
+    eprintln!("Matching mode: {}", if contains_mode { "anywhere (--contains)" } else { "prefix/suffix" });
+    eprintln!("Recovery phrase: {}",
+             if use_mnemonic { "enabled (--use-mnemonic; grinding will be much slower)" } else { "disabled" });
+
+    // The hardest pattern (most constrained characters) governs the overall
+    // ETA, since every thread searches for all patterns simultaneously.
+    let hardest_expected = grind_matches
+        .iter()
+        .map(|m| {
+            let length = pattern_length(m);
+            if contains_mode {
+                expected_attempts_contains(length)
+            } else {
+                expected_attempts(length)
+            }
+        })
+        .fold(0.0, f64::max);
 
     // Shared atomic counter for attempts
     let attempts = Arc::new(AtomicUsize::new(0));
-    let found = Arc::new(AtomicBool::new(false));
     let start_time = Instant::now();
 
     // Spawn worker threads
     let mut handles = vec![];
     for thread_id in 0..num_threads {
-        let desired_prefix_clone = desired_prefix.clone();
+        let grind_matches_clone = Arc::clone(&grind_matches);
         let attempts_clone = Arc::clone(&attempts);
-        let found_clone = Arc::clone(&found);
+        let seed_passphrase_clone = seed_passphrase.clone();
+        let passphrase_clone = passphrase.clone();
+        let start_time_clone = start_time;
+        let contains_mode_clone = contains_mode;
+        let outfile_clone = outfile.clone();
+        let no_outfile_clone = no_outfile;
+        let force_clone = force;
+        let use_mnemonic_clone = use_mnemonic;
 
         let handle = thread::spawn(move || {
             let thread_id = thread_id;
             let mut local_attempts = 0;
 
-            while !found_clone.load(Ordering::Relaxed) {
-                // Generate a random keypair
-                let keypair = Keypair::random();
-                let pubky = keypair.public_key();
-                let pubky_str = pubky.to_string();
+            loop {
+                if grind_matches_clone.iter().all(|m| m.is_complete()) {
+                    return;
+                }
 
-                // Convert to lowercase for case-insensitive comparison
-                let lower_pubky = pubky_str.to_lowercase();
+                // By default, generate a plain random keypair so the hot
+                // loop stays fast (PBKDF2 in `Seed::new` is ~2-3 orders of
+                // magnitude slower than raw randomness). Only pay that cost
+                // on every attempt if the user opted into --use-mnemonic.
+                let (keypair, mnemonic_phrase) = if use_mnemonic_clone {
+                    let mnemonic = Mnemonic::new(mnemonic_type, Language::English);
+                    let keypair = keypair_from_mnemonic(&mnemonic, &seed_passphrase_clone);
+                    (keypair, Some(mnemonic.phrase().to_string()))
+                } else {
+                    (Keypair::random(), None)
+                };
+                let pubky = keypair.public_key();
+                let pubky_bytes = pubky.as_bytes();
 
                 local_attempts += 1;
                 if local_attempts % 1000 == 0 {
@@ -159,78 +587,246 @@ fn main() {
                     // Print status update periodically from just one thread
                     if thread_id == 0 && local_attempts % 10000 == 0 {
                         let total = attempts_clone.load(Ordering::Relaxed);
-                        println!("Still searching... {} attempts so far", total);
+                        let elapsed_secs = start_time_clone.elapsed().as_secs_f64();
+                        let keys_per_sec = if elapsed_secs > 0.0 { total as f64 / elapsed_secs } else { 0.0 };
+                        let percent_of_expected = if hardest_expected > 0.0 {
+                            (total as f64 / hardest_expected) * 100.0
+                        } else {
+                            0.0
+                        };
+                        eprintln!(
+                            "Still searching... {} attempts so far ({:.4}% of expected, ~{:.0} keys/sec)",
+                            total, percent_of_expected, keys_per_sec
+                        );
                     }
                 }
 
-                // Check if the public key starts with the desired prefix
-                if lower_pubky.starts_with(&desired_prefix_clone) {
-                    // Set the found flag to stop other threads
-                    found_clone.store(true, Ordering::Relaxed);
+                // In --contains mode there's no fixed bit offset to check
+                // against raw bytes, so the string form is needed up front.
+                let pubky_str_for_contains = if contains_mode_clone {
+                    Some(pubky.to_string())
+                } else {
+                    None
+                };
+
+                for grind_match in grind_matches_clone.iter() {
+                    if grind_match.is_complete() {
+                        continue;
+                    }
+
+                    let anchor_matches = if contains_mode_clone {
+                        grind_match.matches_anywhere(pubky_str_for_contains.as_ref().unwrap())
+                    } else {
+                        // Cheap raw-byte check first; only format the
+                        // z-base32 string (lowercase already, per the
+                        // alphabet) once a prefix actually matches.
+                        grind_match.matches_prefix(pubky_bytes)
+                    };
+
+                    if !anchor_matches {
+                        continue;
+                    }
+
+                    let pubky_str = pubky_str_for_contains
+                        .clone()
+                        .unwrap_or_else(|| pubky.to_string());
+
+                    if !contains_mode_clone && !grind_match.matches_suffix(&pubky_str) {
+                        continue;
+                    }
+
+                    // Claim this hit; if another thread already finished off
+                    // this pattern's remaining count, keep searching.
+                    let prev = grind_match.count.fetch_update(
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                        |c| if c > 0 { Some(c - 1) } else { None },
+                    );
+                    if prev.is_err() {
+                        continue;
+                    }
 
-                    // Get the secret key in hex format
                     let secret_key_hex = get_secret_key_from_keypair(&keypair);
+                    let match_mode = if contains_mode_clone { "anywhere" } else { "prefix/suffix" };
+
+                    eprintln!(
+                        "Found match for \"{}\" ({}) after {} attempts: {}",
+                        grind_match.label(), match_mode, local_attempts, pubky_str
+                    );
+                    eprintln!("Public key: {}", pubky_str);
+                    eprintln!("Private key: {}", secret_key_hex);
+                    if let Some(phrase) = &mnemonic_phrase {
+                        eprintln!("Recovery phrase ({} words): {}", word_count, phrase);
+                    }
 
-                    // Return the found keys and keypair
-                    return Some((pubky_str, secret_key_hex, keypair, local_attempts));
+                    if no_outfile_clone {
+                        eprintln!("Skipping recovery file (--no-outfile set).");
+                    } else {
+                        let recovery_file_bytes = save_recovery_file(&keypair, &passphrase_clone);
+                        let mode_suffix = if contains_mode_clone { "_anywhere" } else { "" };
+                        // Include the actual pubky in the derived name (not just the
+                        // pattern label) so multiple hits for the same pattern, or
+                        // patterns sharing a label, never collide on one filename.
+                        let filename = outfile_clone.clone().unwrap_or_else(|| {
+                            format!("{}{}_{}_pubky_recovery.pkarr", grind_match.label(), mode_suffix, pubky_str)
+                        });
+
+                        if filename == "-" {
+                            match std::io::stdout().write_all(&recovery_file_bytes) {
+                                Ok(_) => eprintln!("Recovery file bytes written to stdout."),
+                                Err(e) => eprintln!("Failed to write recovery bytes to stdout: {}", e),
+                            }
+                        } else if let Err(e) = check_for_overwrite(&filename, force_clone) {
+                            eprintln!("Recovery file not written: {}", e);
+                        } else {
+                            match File::create(&filename) {
+                                Ok(mut file) => match file.write_all(&recovery_file_bytes) {
+                                    Ok(_) => eprintln!("Recovery file saved: {}", filename),
+                                    Err(e) => eprintln!("Failed to write recovery file: {}", e),
+                                },
+                                Err(e) => eprintln!("Failed to create recovery file: {}", e),
+                            }
+                        }
+                    }
                 }
             }
-
-            // This thread didn't find a match
-            None
         });
 
         handles.push(handle);
     }
 
-    // Wait for results from threads
-    let mut found_thread_attempts = 0;
-    let mut result_pubkey = String::new();
-    let mut result_secret_key = String::new();
-    let mut found_keypair: Option<Keypair> = None;
+    // Warm up briefly so we can turn the raw difficulty estimate above into
+    // a concrete, measured ETA based on this machine's actual keys/second.
+    // Polled in short slices and bailed out early once every pattern is
+    // already satisfied, so a trivial grind isn't held to a ~1s wall-clock
+    // floor just to print an estimate nobody needs anymore.
+    let warmup_duration = std::time::Duration::from_millis(1000);
+    let warmup_poll = std::time::Duration::from_millis(50);
+    let warmup_start = Instant::now();
+    let mut run_already_done = false;
+    while warmup_start.elapsed() < warmup_duration {
+        if grind_matches.iter().all(|m| m.is_complete()) {
+            run_already_done = true;
+            break;
+        }
+        thread::sleep(warmup_poll);
+    }
+    let warmup_keys_per_sec =
+        attempts.load(Ordering::Relaxed) as f64 / warmup_start.elapsed().as_secs_f64();
+
+    if !run_already_done && warmup_keys_per_sec > 0.0 && hardest_expected > 0.0 {
+        let mean_secs = hardest_expected / warmup_keys_per_sec;
+        let median_secs = (hardest_expected * std::f64::consts::LN_2) / warmup_keys_per_sec;
+        let estimate_label = if contains_mode { " (contains-adjusted estimate)" } else { "" };
+        eprintln!(
+            "Estimated at ~{:.0} keys/sec: ~{:.1}s mean, ~{:.1}s median to find the hardest pattern{}",
+            warmup_keys_per_sec, mean_secs, median_secs, estimate_label
+        );
+    }
 
     for handle in handles {
-        if let Ok(Some((pubky, secret_key, keypair, thread_attempts))) = handle.join() {
-            result_pubkey = pubky;
-            result_secret_key = secret_key;
-            found_keypair = Some(keypair);
-            found_thread_attempts = thread_attempts;
-        }
+        let _ = handle.join();
     }
 
     // Calculate total attempts and time
-    let total_attempts = attempts.load(Ordering::Relaxed) + found_thread_attempts;
+    let total_attempts = attempts.load(Ordering::Relaxed);
     let elapsed = start_time.elapsed();
 
-    if let Some(keypair) = found_keypair {
-        println!("Found matching public key after {} attempts and {:.2} seconds:",
-                 total_attempts, elapsed.as_secs_f64());
-        println!("Public key: {}", result_pubkey);
-        println!("Private key: {}", result_secret_key);
-        println!("Average speed: {:.2} keys/second",
-                 total_attempts as f64 / elapsed.as_secs_f64());
-
-        // Create recovery file with provided or default passphrase
-        let recovery_file_bytes = save_recovery_file(&keypair, passphrase);
-
-        // Save the recovery file
-        let filename = format!("{}_pubky_recovery.pkarr", desired_prefix);
-        match File::create(&filename) {
-            Ok(mut file) => {
-                match file.write_all(&recovery_file_bytes) {
-                    Ok(_) => println!("Recovery file saved: {} (with {})",
-                                      filename,
-                                      if has_passphrase {
-                                          "provided passphrase"
-                                      } else {
-                                          "default passphrase"
-                                      }),
-                    Err(e) => println!("Failed to write recovery file: {}", e),
+    eprintln!(
+        "Done: all requested matches found after {} attempts and {:.2} seconds ({:.2} keys/second).",
+        total_attempts,
+        elapsed.as_secs_f64(),
+        total_attempts as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Independent z-base32 string encoder, reimplemented locally so the
+    /// `matches_prefix` fast path can be checked without depending on
+    /// `pubky`'s own encoding (which isn't available in this sandbox).
+    fn zbase32_encode(bytes: &[u8]) -> String {
+        let mut out = String::new();
+        let mut bit_pos = 0usize;
+        let total_bits = bytes.len() * 8;
+        while bit_pos < total_bits {
+            let mut value = 0u8;
+            for shift in (0..5).rev() {
+                let bit = bit_pos;
+                let bit_set = if bit < total_bits {
+                    let byte_idx = bit / 8;
+                    let bit_idx = 7 - (bit % 8);
+                    (bytes[byte_idx] >> bit_idx) & 1
+                } else {
+                    0
+                };
+                value |= bit_set << shift;
+                bit_pos += 1;
+            }
+            out.push(ZBASE32_ALPHABET[value as usize] as char);
+        }
+        out
+    }
+
+    fn sample_keys() -> Vec<[u8; 32]> {
+        vec![
+            [0u8; 32],
+            [0xFFu8; 32],
+            {
+                let mut k = [0u8; 32];
+                for (i, b) in k.iter_mut().enumerate() {
+                    *b = i as u8;
+                }
+                k
+            },
+            {
+                let mut k = [0u8; 32];
+                for (i, b) in k.iter_mut().enumerate() {
+                    *b = (255 - i) as u8;
                 }
+                k
+            },
+        ]
+    }
+
+    #[test]
+    fn matches_prefix_agrees_with_string_starts_with() {
+        for key in sample_keys() {
+            let encoded = zbase32_encode(&key);
+            for len in [0usize, 1, 5, 10, 25, 50, MAX_PREFIX_CHARS] {
+                let prefix: String = encoded.chars().take(len).collect();
+                let grind_match = GrindMatch::new(prefix.clone(), String::new(), 1);
+                assert_eq!(
+                    grind_match.matches_prefix(&key),
+                    encoded.starts_with(&prefix),
+                    "prefix '{}' (len {}) disagreed for key {:?}",
+                    prefix,
+                    len,
+                    key
+                );
             }
-            Err(e) => println!("Failed to create recovery file: {}", e),
         }
-    } else {
-        println!("No matching key found. This shouldn't happen!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn matches_prefix_rejects_mismatched_key() {
+        let key = [0u8; 32];
+        let other_encoded = zbase32_encode(&[0xFFu8; 32]);
+        let prefix: String = other_encoded.chars().take(10).collect();
+        let grind_match = GrindMatch::new(prefix, String::new(), 1);
+        assert!(!grind_match.matches_prefix(&key));
+    }
+
+    #[test]
+    fn contains_estimate_is_cheaper_than_anchored() {
+        for length in 1..=10 {
+            assert!(
+                expected_attempts_contains(length) <= expected_attempts(length),
+                "contains estimate should never exceed the anchored estimate for length {}",
+                length
+            );
+        }
+    }
+}